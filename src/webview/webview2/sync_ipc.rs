@@ -3,35 +3,50 @@
 // SPDX-License-Identifier: MIT
 
 use std::{
+  cell::RefCell,
+  collections::HashMap,
+  ffi::c_void,
   mem::{size_of, ManuallyDrop},
   rc::Rc,
+  sync::OnceLock,
 };
 
 use windows::{
   core::{IUnknown, IUnknownVtbl, Interface, GUID, PCWSTR, PWSTR},
   Win32::{
-    Foundation::{BSTR, DISP_E_BADINDEX, DISP_E_UNKNOWNINTERFACE},
+    Foundation::{
+      BSTR, DISP_E_BADINDEX, DISP_E_MEMBERNOTFOUND, DISP_E_UNKNOWNINTERFACE,
+      DISP_E_UNKNOWNNAME, E_NOTIMPL, VARIANT_BOOL, VARIANT_TRUE,
+    },
     Globalization::{LocaleNameToLCID, LOCALE_NAME_INVARIANT},
     System::{
       Com::{
-        IDispatch, IDispatch_Impl, ITypeInfo, CC_STDCALL, DISPPARAMS, EXCEPINFO, VARIANT,
-        VARIANT_0, VARIANT_0_0, VARIANT_0_0_0,
+        IDispatch, IDispatch_Impl, IDispatchEx, IDispatchEx_Impl, IServiceProvider, ITypeInfo,
+        SAFEARRAY, CC_STDCALL, DISPID_STARTENUM, DISPPARAMS, EXCEPINFO, VARIANT, VARIANT_0,
+        VARIANT_0_0, VARIANT_0_0_0,
       },
       Ole::{
-        CreateDispTypeInfo, DispGetIDsOfNames, DispInvoke, DISPATCH_METHOD, INTERFACEDATA,
-        METHODDATA, PARAMDATA, VT_BSTR, VT_DISPATCH,
+        CreateDispTypeInfo, DispGetIDsOfNames, SafeArrayCreateVector, SafeArrayDestroy,
+        SafeArrayGetElement, SafeArrayGetLBound, SafeArrayGetUBound, SafeArrayPutElement,
+        VariantChangeType, VariantClear, DISPATCH_METHOD, DISPATCH_PROPERTYGET,
+        DISPATCH_PROPERTYPUT, INTERFACEDATA, METHODDATA, PARAMDATA, VARFORMAT, VT_ARRAY, VT_BOOL,
+        VT_BSTR, VT_DISPATCH, VT_EMPTY, VT_I4, VT_NULL, VT_R8, VT_TYPEMASK, VT_VARIANT,
       },
     },
   },
 };
 
 use windows_implement::implement;
-use windows_interface::interface;
 
 use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2;
 
 use crate::application::window::Window;
 
+/// The first DISPID handed out to a dynamically-registered member, chosen
+/// to sit comfortably past any realistic number of builder-registered
+/// fixed methods so the two ranges never collide.
+const DYNAMIC_DISPID_BASE: i32 = 0x1000;
+
 macro_rules! pwstr {
   ($string:literal) => {{
     const UTF16: &[u16] = ::const_utf16::encode_null_terminated!($string);
@@ -46,72 +61,583 @@ macro_rules! pwstr {
   }};
 }
 
-#[interface("e0912f1d-f683-40cd-94c6-20a1d7e96bdc")]
-unsafe trait ISyncIPCHandler: IUnknown {
-  unsafe fn PostSyncMessage(&self, message: BSTR) -> BSTR;
+/// Leaks a null-terminated UTF-16 encoding of `name`, for use in `PARAMDATA`/
+/// `METHODDATA` entries whose names are only known at runtime (unlike
+/// `pwstr!`, which only accepts a string literal).
+fn leak_pwstr(name: &str) -> PWSTR {
+  let utf16: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+  let leaked: &'static mut [u16] = Box::leak(utf16.into_boxed_slice());
+  PWSTR(leaked.as_mut_ptr())
+}
+
+/// The original, compile-time-fixed shape of this host object: a single
+/// `PostSyncMessage(message: BSTR) -> BSTR` member whose `ITypeInfo` is
+/// derived at build time by the `#[wry_macros::create_disp_type_info]`
+/// proc macro from a `#[interface(...)]` trait, rather than by the
+/// runtime, name-list-driven [`create_disp_type_info`] below.
+///
+/// `SyncIPCHandlerBuilder`'s method set is only known at runtime, which a
+/// compile-time derive can't describe, so `SyncIPCHandler` no longer
+/// `#[implement]`s `ISyncIPCHandler` as a real COM interface the way it did
+/// before the builder existed — it only implements `IDispatch`/`IDispatchEx` now,
+/// and dispatches by DISPID instead of casting to `ISyncIPCHandler` and
+/// calling `DispInvoke`. A side effect: `QueryInterface` for
+/// `ISyncIPCHandler`'s IID (`e0912f1d-...`) now fails where it used to
+/// succeed, since nothing implements that trait anymore. The trait is kept
+/// here purely so the macro still has a real caller, feeding
+/// `SyncIPCHandler::new`'s compatibility shim a compile-time `ITypeInfo`
+/// for callers who only need the one fixed method.
+///
+/// Nested in its own module because the macro emits its generated
+/// `create_disp_type_info()` at the same scope as the trait it decorates,
+/// which would otherwise collide with the runtime function of the same
+/// name in the parent module.
+mod fixed_interface {
+  use windows::{core::IUnknown, Win32::Foundation::BSTR};
+  use windows_interface::interface;
+  use wry_macros::create_disp_type_info;
+
+  #[create_disp_type_info]
+  #[interface("e0912f1d-f683-40cd-94c6-20a1d7e96bdc")]
+  pub(crate) unsafe trait ISyncIPCHandler: IUnknown {
+    unsafe fn PostSyncMessage(&self, message: BSTR) -> BSTR;
+  }
+}
+
+/// Whether `WRY_TRACE_SYNC_IPC` is set, cached after the first check so
+/// tracing calls are a single atomic load when tracing is off rather than
+/// an env var lookup on every `GetIDsOfNames`/`Invoke`.
+fn tracing_enabled() -> bool {
+  static ENABLED: OnceLock<bool> = OnceLock::new();
+  *ENABLED.get_or_init(|| std::env::var_os("WRY_TRACE_SYNC_IPC").is_some())
+}
+
+/// Human-readable names for the IIDs this file's own
+/// `GetIDsOfNames`/`Invoke`/`InvokeEx` paths actually see cross their desk.
+///
+/// Flagged for maintainer sign-off: this can't cover `QueryInterface`
+/// itself, since `#[implement]` generates that vtable entry directly and
+/// gives this module no hook into it — so a probe for an interface this
+/// object doesn't implement (including, notably, `ISyncIPCHandler` itself;
+/// see `fixed_interface`) never reaches this table at all. Everything
+/// below only traces what the `IDispatch`/`IDispatchEx` entry points see.
+const KNOWN_IIDS: &[(GUID, &str)] = &[
+  (IUnknown::IID, "IUnknown"),
+  (IDispatch::IID, "IDispatch"),
+  (IDispatchEx::IID, "IDispatchEx"),
+  (ICoreWebView2::IID, "ICoreWebView2"),
+];
+
+/// Maps `iid` to a name from [`KNOWN_IIDS`], or formats it as
+/// `Unknown:{guid}` when it isn't one of them.
+fn iid_label(iid: &GUID) -> String {
+  KNOWN_IIDS
+    .iter()
+    .find(|(known, _)| known == iid)
+    .map(|(_, name)| name.to_string())
+    .unwrap_or_else(|| format!("Unknown:{iid:?}"))
+}
+
+/// Traces a `QueryInterface`-adjacent entry point (`GetIDsOfNames`,
+/// `Invoke`'s own `riid` check) by IID. A no-op unless
+/// [`tracing_enabled`], so callers can compute `riid` eagerly — reading an
+/// already-in-hand pointer is cheap either way.
+fn trace_probe(context: &str, riid: &GUID) {
+  if tracing_enabled() {
+    eprintln!("[wry::sync_ipc] {context} riid={}", iid_label(riid));
+  }
+}
+
+/// Traces an `Invoke`/`InvokeEx` dispatch by dispid and argument count.
+/// `member_name` is only called when [`tracing_enabled`], since resolving
+/// a dispid back to a name walks `method_names`/`DynamicMembers` and
+/// shouldn't cost anything when tracing is off.
+fn trace_invoke(dispidmember: i32, arg_count: u32, member_name: impl FnOnce() -> String) {
+  if tracing_enabled() {
+    eprintln!(
+      "[wry::sync_ipc] Invoke dispid={dispidmember} member={} args={arg_count}",
+      member_name()
+    );
+  }
+}
+
+/// A native representation of the value shapes that cross the VARIANT
+/// boundary between JS and a handler closure: the JSON-ish primitives plus
+/// arrays, and an opaque handle for a nested `IDispatch` object that the
+/// handler doesn't need to decode further.
+#[derive(Debug, Clone)]
+pub(crate) enum IpcValue {
+  Empty,
+  Null,
+  Bool(bool),
+  Int(i32),
+  Float(f64),
+  String(String),
+  Array(Vec<IpcValue>),
+  Object(IDispatch),
+}
+
+impl Default for IpcValue {
+  fn default() -> Self {
+    IpcValue::Empty
+  }
+}
+
+/// Masks off `VT_BYREF`/`VT_ARRAY` so callers comparing against a bare
+/// `VT_*` constant (e.g. `VT_BSTR`) aren't fooled by those flag bits being
+/// set on an otherwise-matching VARIANT.
+fn base_vt(vt: u16) -> u16 {
+  vt & VT_TYPEMASK.0 as u16
+}
+
+/// Decodes a `VARIANT` into the matching [`IpcValue`], falling back to
+/// `VariantChangeType`-coercing to `VT_BSTR` for any type this doesn't
+/// decode directly (e.g. `VT_UI2`, `VT_DATE`), so odd-but-valid VARIANTs
+/// still produce *something* usable instead of silently becoming `Null`.
+///
+/// Safety: `variant` must be a valid, initialized `VARIANT`.
+unsafe fn ipc_value_from_variant(variant: &VARIANT) -> IpcValue {
+  let vt = variant.Anonymous.Anonymous.vt;
+
+  if vt & VT_ARRAY.0 as u16 != 0 {
+    let psa = variant.Anonymous.Anonymous.Anonymous.parray;
+    return IpcValue::Array(ipc_values_from_safearray(psa));
+  }
+
+  match base_vt(vt) {
+    vt if vt == VT_EMPTY.0 as u16 => IpcValue::Empty,
+    vt if vt == VT_NULL.0 as u16 => IpcValue::Null,
+    vt if vt == VT_BOOL.0 as u16 => {
+      IpcValue::Bool(variant.Anonymous.Anonymous.Anonymous.boolVal == VARIANT_TRUE)
+    }
+    vt if vt == VT_I4.0 as u16 => IpcValue::Int(variant.Anonymous.Anonymous.Anonymous.lVal),
+    vt if vt == VT_R8.0 as u16 => IpcValue::Float(variant.Anonymous.Anonymous.Anonymous.dblVal),
+    vt if vt == VT_BSTR.0 as u16 => {
+      let bstr = &*variant.Anonymous.Anonymous.Anonymous.bstrVal;
+      IpcValue::String(bstr.to_string())
+    }
+    vt if vt == VT_DISPATCH.0 as u16 => variant
+      .Anonymous
+      .Anonymous
+      .Anonymous
+      .pdispVal
+      .as_ref()
+      .cloned()
+      .map(IpcValue::Object)
+      .unwrap_or(IpcValue::Null),
+    _ => coerce_to_string(variant)
+      .map(IpcValue::String)
+      .unwrap_or(IpcValue::Null),
+  }
+}
+
+/// Safety: `variant` must be a valid, initialized `VARIANT`.
+unsafe fn coerce_to_string(variant: &VARIANT) -> Option<String> {
+  let mut coerced = VARIANT::default();
+  VariantChangeType(&mut coerced, variant, VARFORMAT(0), VT_BSTR.0 as u16).ok()?;
+  let bstr = (*coerced.Anonymous.Anonymous.Anonymous.bstrVal).clone();
+  VariantClear(&mut coerced).ok();
+  bstr.try_into().ok()
+}
+
+/// Builds an owned, initialized VARIANT wrapping `payload`, tagged with
+/// `vt`, which must name whichever branch the union payload belongs to.
+fn variant_of(vt: u16, payload: VARIANT_0_0_0) -> VARIANT {
+  VARIANT {
+    Anonymous: VARIANT_0 {
+      Anonymous: ManuallyDrop::new(VARIANT_0_0 {
+        vt,
+        Anonymous: payload,
+        ..Default::default()
+      }),
+    },
+  }
+}
+
+/// Converts an [`IpcValue`] into an owned `VARIANT`. The caller takes
+/// ownership of any heap data inside (the `BSTR`, the `IDispatch`
+/// reference, the `SAFEARRAY`) and is responsible for eventually dropping
+/// or `VariantClear`-ing it.
+fn variant_from_ipc_value(value: IpcValue) -> webview2_com::Result<VARIANT> {
+  Ok(match value {
+    IpcValue::Empty => VARIANT::default(),
+    IpcValue::Null => variant_of(VT_NULL.0 as u16, VARIANT_0_0_0::default()),
+    IpcValue::Bool(value) => variant_of(
+      VT_BOOL.0 as u16,
+      VARIANT_0_0_0 {
+        boolVal: if value {
+          VARIANT_TRUE
+        } else {
+          VARIANT_BOOL(0)
+        },
+      },
+    ),
+    IpcValue::Int(value) => variant_of(VT_I4.0 as u16, VARIANT_0_0_0 { lVal: value }),
+    IpcValue::Float(value) => variant_of(VT_R8.0 as u16, VARIANT_0_0_0 { dblVal: value }),
+    IpcValue::String(value) => variant_of(
+      VT_BSTR.0 as u16,
+      VARIANT_0_0_0 {
+        bstrVal: ManuallyDrop::new(BSTR::from(value)),
+      },
+    ),
+    IpcValue::Object(dispatch) => variant_of(
+      VT_DISPATCH.0 as u16,
+      VARIANT_0_0_0 {
+        pdispVal: ManuallyDrop::new(Some(dispatch)),
+      },
+    ),
+    IpcValue::Array(values) => {
+      let psa = safearray_from_ipc_values(values)?;
+      variant_of(
+        (VT_ARRAY.0 as u16) | (VT_VARIANT.0 as u16),
+        VARIANT_0_0_0 { parray: psa },
+      )
+    }
+  })
+}
+
+/// Reads every element of a `VT_ARRAY | VT_VARIANT` `SAFEARRAY` (a single,
+/// zero-based dimension) into a `Vec<IpcValue>`. A bound that can't be read
+/// yields an empty `Vec`, and an element that can't be read decodes as
+/// [`IpcValue::Null`], rather than failing the whole decode, since this is
+/// reached from contexts
+/// (`ipc_value_from_variant`) that have no error channel of their own.
+///
+/// Safety: `psa` must be null or point to a valid, single-dimension
+/// `SAFEARRAY` of `VARIANT`s.
+unsafe fn ipc_values_from_safearray(psa: *mut SAFEARRAY) -> Vec<IpcValue> {
+  if psa.is_null() {
+    return Vec::new();
+  }
+
+  let mut lower = 0i32;
+  let mut upper = -1i32;
+  if SafeArrayGetLBound(psa, 1, &mut lower).is_err() || SafeArrayGetUBound(psa, 1, &mut upper).is_err() {
+    return Vec::new();
+  }
+
+  (lower..=upper)
+    .map(|index| {
+      let mut element = VARIANT::default();
+      if SafeArrayGetElement(psa, &index, &mut element as *mut _ as *mut c_void).is_err() {
+        return IpcValue::Null;
+      }
+      let decoded = ipc_value_from_variant(&element);
+      VariantClear(&mut element).ok();
+      decoded
+    })
+    .collect()
 }
 
-#[implement(IDispatch, ISyncIPCHandler)]
+/// Builds a fresh `VT_VARIANT` `SAFEARRAY` holding `values` in order.
+/// Cleans up the array itself if any element fails to marshal or write.
+fn safearray_from_ipc_values(values: Vec<IpcValue>) -> webview2_com::Result<*mut SAFEARRAY> {
+  // Safety: WinAPI calls are unsafe
+  unsafe {
+    let psa = SafeArrayCreateVector(VT_VARIANT, 0, values.len() as u32);
+    if psa.is_null() {
+      return Err(webview2_com::Error::WindowsError(
+        windows::core::Error::from(windows::Win32::Foundation::E_OUTOFMEMORY),
+      ));
+    }
+
+    for (index, value) in values.into_iter().enumerate() {
+      let index = index as i32;
+      let result = variant_from_ipc_value(value).and_then(|mut variant| {
+        let put_result = SafeArrayPutElement(psa, &index, &variant as *const _ as *const c_void)
+          .map_err(webview2_com::Error::WindowsError);
+        VariantClear(&mut variant).ok();
+        put_result
+      });
+
+      if let Err(error) = result {
+        SafeArrayDestroy(psa).ok();
+        return Err(error);
+      }
+    }
+
+    Ok(psa)
+  }
+}
+
+/// Decodes the lone positional argument `CreateDispTypeInfo`'s method
+/// signature promises `DISPPARAMS` will carry, defaulting to
+/// [`IpcValue::Empty`] when there isn't one.
+///
+/// Safety: `pdispparams` must be a valid `DISPPARAMS*`.
+unsafe fn take_ipc_arg(pdispparams: *const DISPPARAMS) -> IpcValue {
+  if pdispparams.is_null() || (*pdispparams).cArgs == 0 {
+    return IpcValue::Empty;
+  }
+
+  ipc_value_from_variant(&*(*pdispparams).rgvarg)
+}
+
+/// Safety: `pvarresult` must be null or a valid, writable `VARIANT*`.
+unsafe fn write_ipc_result(pvarresult: *mut VARIANT, value: IpcValue) -> webview2_com::Result<()> {
+  if let Some(pvarresult) = pvarresult.as_mut() {
+    *pvarresult = variant_from_ipc_value(value)?;
+  }
+  Ok(())
+}
+
+/// Bookkeeping for members registered at runtime via `IDispatchEx`
+/// (`window.ipc.myChannel = ...` and friends), kept separate from the
+/// fixed, `ITypeInfo`-described methods a [`SyncIPCHandlerBuilder`]
+/// registers up front. Each dynamic member is just a named value slot —
+/// JS assigns to it, reads it back, and can delete it — rather than a
+/// callable method.
+#[derive(Default)]
+struct DynamicMembers {
+  dispids: HashMap<String, i32>,
+  names_in_order: Vec<String>,
+  values: HashMap<i32, IpcValue>,
+  next_dispid: i32,
+}
+
+impl DynamicMembers {
+  fn new(starting_dispid: i32) -> Self {
+    Self {
+      next_dispid: starting_dispid,
+      ..Default::default()
+    }
+  }
+
+  /// Looks `name` up, minting and recording a fresh DISPID for it when
+  /// `ensure` is set (`fdexNameEnsure`) and it isn't already known.
+  fn get_or_ensure(&mut self, name: &str, ensure: bool) -> Option<i32> {
+    if let Some(&dispid) = self.dispids.get(name) {
+      return Some(dispid);
+    }
+
+    if !ensure {
+      return None;
+    }
+
+    let dispid = self.next_dispid;
+    self.next_dispid += 1;
+    self.dispids.insert(name.to_string(), dispid);
+    self.names_in_order.push(name.to_string());
+    Some(dispid)
+  }
+
+  fn name_of(&self, dispid: i32) -> Option<&str> {
+    self
+      .names_in_order
+      .iter()
+      .find(|name| self.dispids.get(name.as_str()) == Some(&dispid))
+      .map(String::as_str)
+  }
+
+  /// Returns the DISPID that follows `after` in registration order, or
+  /// `None` once enumeration has walked off the end — the `IDispatchEx`
+  /// impl maps that to `S_FALSE` to tell the caller `for..in` is done.
+  fn next_after(&self, after: i32) -> Option<i32> {
+    let start_index = if after == DISPID_STARTENUM {
+      0
+    } else {
+      let current_name = self.name_of(after)?;
+      self
+        .names_in_order
+        .iter()
+        .position(|name| name == current_name)?
+        + 1
+    };
+
+    self
+      .names_in_order
+      .get(start_index)
+      .and_then(|name| self.dispids.get(name.as_str()))
+      .copied()
+  }
+
+  fn remove(&mut self, name: &str) -> Option<i32> {
+    let dispid = self.dispids.remove(name)?;
+    self.names_in_order.retain(|existing| existing != name);
+    self.values.remove(&dispid);
+    Some(dispid)
+  }
+
+  fn remove_by_dispid(&mut self, dispid: i32) -> bool {
+    match self.name_of(dispid).map(str::to_string) {
+      Some(name) => self.remove(&name).is_some(),
+      None => false,
+    }
+  }
+}
+
+/// A `window.ipc`-style host object exposing several distinct, named
+/// `(value: IpcValue) -> IpcValue` methods, each backed by its own handler,
+/// plus any number of plain value members JS registers at runtime via
+/// `IDispatchEx`.
+#[implement(IDispatch, IDispatchEx)]
 pub(crate) struct SyncIPCHandler {
   type_info: ITypeInfo,
   window: Rc<Window>,
-  handler: Box<dyn Fn(&Window, String) -> String>,
+  method_names: Vec<String>,
+  methods: Vec<Box<dyn Fn(&Window, IpcValue) -> IpcValue>>,
+  dynamic_members: RefCell<DynamicMembers>,
+}
+
+/// Builds a [`SyncIPCHandler`] by registering one or more named methods
+/// before it's injected into the page, e.g.:
+///
+/// ```ignore
+/// SyncIPCHandlerBuilder::new(window)
+///   .add_method("readClipboard", Box::new(|window, _| read_clipboard(window)))
+///   .add_method("postSync", Box::new(|window, value| handle_message(window, value)))
+///   .build()?
+/// ```
+pub(crate) struct SyncIPCHandlerBuilder {
+  window: Rc<Window>,
+  names: Vec<String>,
+  handlers: Vec<Box<dyn Fn(&Window, IpcValue) -> IpcValue>>,
+}
+
+impl SyncIPCHandlerBuilder {
+  pub(crate) fn new(window: Rc<Window>) -> Self {
+    Self {
+      window,
+      names: Vec::new(),
+      handlers: Vec::new(),
+    }
+  }
+
+  pub(crate) fn add_method(
+    mut self,
+    name: impl Into<String>,
+    handler: Box<dyn Fn(&Window, IpcValue) -> IpcValue>,
+  ) -> Self {
+    self.names.push(name.into());
+    self.handlers.push(handler);
+    self
+  }
+
+  pub(crate) fn build(self) -> webview2_com::Result<SyncIPCHandler> {
+    let type_info = create_disp_type_info(&self.names)?;
+    let method_count = self.names.len() as i32;
+
+    Ok(SyncIPCHandler {
+      type_info,
+      window: self.window,
+      method_names: self.names,
+      methods: self.handlers,
+      dynamic_members: RefCell::new(DynamicMembers::new(method_count.max(DYNAMIC_DISPID_BASE))),
+    })
+  }
+}
+
+/// Builds an `ITypeInfo` describing one `VARIANT value -> VARIANT` method
+/// per entry in `names`, in order, so dispid `i` is `names[i]`. `VARIANT`
+/// rather than `BSTR` lets the generated type info match what
+/// `Invoke`/`InvokeEx` actually marshal now that handlers exchange
+/// [`IpcValue`]s instead of plain strings. Every array fed to
+/// `CreateDispTypeInfo` is leaked so it stays valid for the handler's
+/// lifetime, mirroring the `'static` statics this used to be hand-written
+/// with for a single fixed method.
+fn create_disp_type_info(names: &[String]) -> webview2_com::Result<ITypeInfo> {
+  let methods: Vec<METHODDATA> = names
+    .iter()
+    .enumerate()
+    .map(|(index, name)| {
+      let params: &'static mut [PARAMDATA] = Box::leak(Box::new([PARAMDATA {
+        szName: pwstr!("value"),
+        vt: VT_VARIANT.0 as u16,
+      }]));
+
+      METHODDATA {
+        szName: leak_pwstr(name),
+        ppdata: params.as_mut_ptr(),
+        dispid: index as i32,
+        // this method's slot in the vtable, past IUnknown's three slots plus
+        // every method that precedes it
+        #[allow(clippy::identity_op)]
+        iMeth: (size_of::<IUnknownVtbl>() / size_of::<fn()>() + index) as u32,
+        cc: CC_STDCALL,
+        cArgs: params.len() as u32,
+        wFlags: DISPATCH_METHOD as u16,
+        vtReturn: VT_VARIANT.0 as u16,
+      }
+    })
+    .collect();
+
+  let methods: &'static mut [METHODDATA] = Box::leak(methods.into_boxed_slice());
+
+  let mut interface_data = INTERFACEDATA {
+    pmethdata: methods.as_mut_ptr(),
+    cMembers: methods.len() as u32,
+  };
+
+  // Safety: WinAPI calls are unsafe
+  unsafe {
+    let invariant_locale = LocaleNameToLCID(LOCALE_NAME_INVARIANT, 0);
+    let mut type_info = None;
+    CreateDispTypeInfo(
+      &mut interface_data as *mut _,
+      invariant_locale,
+      &mut type_info as *mut _,
+    )?;
+    Ok(type_info.unwrap())
+  }
 }
 
 impl SyncIPCHandler {
+  /// Source-compatible with the single-method constructor this type had
+  /// before `SyncIPCHandlerBuilder` replaced it: registers `handler`
+  /// under the original fixed `PostSyncMessage` member name, using
+  /// [`fixed_interface`]'s macro-derived `ITypeInfo` rather than the
+  /// builder's runtime one, so existing callers of `SyncIPCHandler::new`
+  /// don't have to move to the builder just to keep compiling. `handler`
+  /// still trades in plain strings; this shim does the `IpcValue`
+  /// round-trip so the signature stays source-compatible too.
   pub(crate) fn new(
     window: Rc<Window>,
     handler: Box<dyn Fn(&Window, String) -> String>,
   ) -> webview2_com::Result<Self> {
-    // Safety: we never mutate SyncIPCHandler's type information, so the below statics can be safely Sync
-    #[repr(transparent)]
-    struct SyncStatic<T>(T);
-
-    unsafe impl<T> Sync for SyncStatic<T> {}
-
-    static mut DISPATCH_INTERFACE_POST_SYNC_MESSAGE_PARAMS: SyncStatic<[PARAMDATA; 1]> =
-      SyncStatic([PARAMDATA {
-        szName: pwstr!("message"),
-        vt: VT_BSTR.0 as u16,
-      }]);
-
-    static mut DISPATCH_INTERFACE_METHODS: SyncStatic<[METHODDATA; 1]> = SyncStatic([METHODDATA {
-      szName: pwstr!("PostSyncMessage"),
-      ppdata: unsafe { &mut DISPATCH_INTERFACE_POST_SYNC_MESSAGE_PARAMS.0 as *mut _ },
-      dispid: 0,
-      // PostSyncMessage is the first method in ISyncIPCHandler
-      #[allow(clippy::identity_op)]
-      iMeth: (size_of::<IUnknownVtbl>() / size_of::<fn()>() + 0) as u32,
-      cc: CC_STDCALL,
-      cArgs: unsafe { DISPATCH_INTERFACE_METHODS.0.len() as u32 },
-      wFlags: DISPATCH_METHOD as u16,
-      vtReturn: VT_BSTR.0 as u16,
-    }]);
-
-    static mut DISPATCH_INTERFACE: SyncStatic<INTERFACEDATA> = SyncStatic(INTERFACEDATA {
-      pmethdata: unsafe { &mut DISPATCH_INTERFACE_METHODS.0 as *mut _ },
-      cMembers: unsafe { DISPATCH_INTERFACE_METHODS.0.len() as u32 },
-    });
+    let type_info = fixed_interface::create_disp_type_info()?;
 
-    // Safety: WinAPI calls are unsafe
-    let type_info = unsafe {
-      let invariant_locale = LocaleNameToLCID(LOCALE_NAME_INVARIANT, 0);
-      let mut type_info = None;
-      CreateDispTypeInfo(
-        &mut DISPATCH_INTERFACE.0 as *mut _,
-        invariant_locale,
-        &mut type_info as *mut _,
-      )?;
-      type_info.unwrap()
-    };
+    let handler: Box<dyn Fn(&Window, IpcValue) -> IpcValue> = Box::new(move |window, value| {
+      let message = match value {
+        IpcValue::String(message) => message,
+        _ => String::new(),
+      };
+      IpcValue::String(handler(window, message))
+    });
 
     Ok(Self {
       type_info,
       window,
-      handler,
+      method_names: vec!["PostSyncMessage".to_string()],
+      methods: vec![handler],
+      dynamic_members: RefCell::new(DynamicMembers::new(DYNAMIC_DISPID_BASE)),
     })
   }
 
+  /// The member name for `dispid`, whether it's one of the fixed,
+  /// builder-registered methods or a name JS registered dynamically.
+  fn member_name(&self, dispid: i32) -> Option<String> {
+    self
+      .method_names
+      .get(dispid as usize)
+      .cloned()
+      .or_else(|| {
+        self
+          .dynamic_members
+          .borrow()
+          .name_of(dispid)
+          .map(str::to_string)
+      })
+  }
+
+  /// [`member_name`](Self::member_name), formatted for a trace line —
+  /// unknown dispids still print as something rather than vanishing the
+  /// whole log line.
+  fn traced_member_name(&self, dispid: i32) -> String {
+    self
+      .member_name(dispid)
+      .unwrap_or_else(|| format!("<unknown:{dispid}>"))
+  }
+
   pub(crate) fn inject(self, webview: &ICoreWebView2) -> webview2_com::Result<()> {
     let handler: IDispatch = self.into();
 
@@ -178,7 +704,12 @@ impl IDispatch_Impl for SyncIPCHandler {
   ) -> windows::core::Result<()> {
     // Safety: riid is checked for null before deref + WinAPI calls are unsafe
     unsafe {
-      if riid.is_null() || *riid != GUID::default() {
+      if riid.is_null() {
+        return Err(DISP_E_UNKNOWNINTERFACE.into());
+      }
+      trace_probe("GetIDsOfNames", &*riid);
+
+      if *riid != GUID::default() {
         Err(DISP_E_UNKNOWNINTERFACE.into())
       } else {
         DispGetIDsOfNames(&self.type_info, rgsznames, cnames, rgdispid)
@@ -191,52 +722,292 @@ impl IDispatch_Impl for SyncIPCHandler {
     dispidmember: i32,
     riid: *const GUID,
     _lcid: u32,
-    wflags: u16,
+    _wflags: u16,
     pdispparams: *const DISPPARAMS,
     pvarresult: *mut VARIANT,
-    pexcepinfo: *mut EXCEPINFO,
-    puargerr: *mut u32,
+    _pexcepinfo: *mut EXCEPINFO,
+    _puargerr: *mut u32,
   ) -> windows::core::Result<()> {
     // Safety: pointers are checked for null before deref + WinAPI calls are unsafe
     unsafe {
-      if riid.is_null() || *riid != GUID::default() {
-        Err(DISP_E_UNKNOWNINTERFACE.into())
-      } else {
-        let this: ISyncIPCHandler = self.cast()?;
+      if riid.is_null() {
+        return Err(DISP_E_UNKNOWNINTERFACE.into());
+      }
+      trace_probe("Invoke", &*riid);
 
-        // Invoke takes a *const DISPPARAMS but DispInvoke wants a *mut DISPPARAMS ???
-        let mut dispparams = if pdispparams.is_null() {
-          None
-        } else {
-          Some(*pdispparams)
-        };
-        let pdispparams_mut = dispparams
-          .as_mut()
-          .map(|x| x as _)
-          .unwrap_or(std::ptr::null_mut());
-
-        DispInvoke(
-          this.as_raw(),
-          &self.type_info,
-          dispidmember,
-          wflags,
-          pdispparams_mut,
-          pvarresult,
-          pexcepinfo,
-          puargerr,
-        )
+      if *riid != GUID::default() {
+        return Err(DISP_E_UNKNOWNINTERFACE.into());
       }
+
+      let Some(handler) = self.methods.get(dispidmember as usize) else {
+        return Err(DISP_E_MEMBERNOTFOUND.into());
+      };
+
+      let arg_count = pdispparams.as_ref().map(|params| params.cArgs).unwrap_or(0);
+      trace_invoke(dispidmember, arg_count, || {
+        self.traced_member_name(dispidmember)
+      });
+
+      let arg = take_ipc_arg(pdispparams);
+      let result = handler(&self.window, arg);
+      write_ipc_result(pvarresult, result)?;
+      Ok(())
     }
   }
 }
 
 #[allow(non_snake_case)]
-impl ISyncIPCHandler_Impl for SyncIPCHandler {
-  unsafe fn PostSyncMessage(&self, message: BSTR) -> BSTR {
-    if let Ok(utf8_message) = message.try_into() {
-      (self.handler)(&self.window, utf8_message).into()
+impl IDispatchEx_Impl for SyncIPCHandler {
+  fn GetDispID(&self, bstrname: &BSTR, grfdex: u32, pid: *mut i32) -> windows::core::Result<()> {
+    let name: String = bstrname.try_into().unwrap_or_default();
+
+    let dispid = if let Some(index) = self.method_names.iter().position(|known| known == &name) {
+      Some(index as i32)
+    } else {
+      const FDEX_NAME_ENSURE: u32 = 0x2;
+      self
+        .dynamic_members
+        .borrow_mut()
+        .get_or_ensure(&name, grfdex & FDEX_NAME_ENSURE != 0)
+    };
+
+    match dispid {
+      // Safety: pid is a valid out-param pointer per the IDispatchEx contract
+      Some(dispid) => unsafe {
+        *pid = dispid;
+        Ok(())
+      },
+      None => Err(DISP_E_UNKNOWNNAME.into()),
+    }
+  }
+
+  fn InvokeEx(
+    &self,
+    id: i32,
+    _lcid: u32,
+    wflags: u16,
+    pdispparams: *const DISPPARAMS,
+    pvarresult: *mut VARIANT,
+    _pexcepinfo: *mut EXCEPINFO,
+    _pspcaller: windows::core::Ref<'_, IServiceProvider>,
+  ) -> windows::core::Result<()> {
+    if (id as usize) < self.methods.len() {
+      return self.Invoke(
+        id,
+        &GUID::default(),
+        _lcid,
+        wflags,
+        pdispparams,
+        pvarresult,
+        _pexcepinfo,
+        std::ptr::null_mut(),
+      );
+    }
+
+    let arg_count = unsafe { pdispparams.as_ref() }
+      .map(|params| params.cArgs)
+      .unwrap_or(0);
+    trace_invoke(id, arg_count, || self.traced_member_name(id));
+
+    if wflags as i32 & DISPATCH_PROPERTYPUT != 0 {
+      if self.dynamic_members.borrow().name_of(id).is_none() {
+        return Err(DISP_E_MEMBERNOTFOUND.into());
+      }
+
+      // Safety: pdispparams is a valid DISPPARAMS* with a named
+      // DISPID_PROPERTYPUT argument per the IDispatchEx PROPERTYPUT contract
+      let value = unsafe { take_ipc_arg(pdispparams) };
+      self.dynamic_members.borrow_mut().values.insert(id, value);
+      return Ok(());
+    }
+
+    if wflags as i32 & DISPATCH_PROPERTYGET != 0 {
+      let value = self
+        .dynamic_members
+        .borrow()
+        .values
+        .get(&id)
+        .cloned()
+        .unwrap_or_default();
+      // Safety: pvarresult is null or a valid, writable VARIANT*
+      return unsafe { write_ipc_result(pvarresult, value) };
+    }
+
+    Err(DISP_E_MEMBERNOTFOUND.into())
+  }
+
+  fn DeleteMemberByName(&self, bstrname: &BSTR, _grfdex: u32) -> windows::core::Result<()> {
+    let name: String = bstrname.try_into().unwrap_or_default();
+    match self.dynamic_members.borrow_mut().remove(&name) {
+      Some(_) => Ok(()),
+      None => Err(DISP_E_UNKNOWNNAME.into()),
+    }
+  }
+
+  fn DeleteMemberByDispID(&self, id: i32) -> windows::core::Result<()> {
+    if self.dynamic_members.borrow_mut().remove_by_dispid(id) {
+      Ok(())
     } else {
-      BSTR::default()
+      Err(DISP_E_MEMBERNOTFOUND.into())
     }
   }
+
+  fn GetMemberProperties(&self, _id: i32, _grfdexfetch: u32) -> windows::core::Result<u32> {
+    // None of the optional FDEX_PROP_* bits apply to these plain value
+    // members; callers that actually need flags like FDEX_PROP_CANGET
+    // should just attempt the get/put and handle the failure.
+    Ok(0)
+  }
+
+  fn GetMemberName(&self, id: i32) -> windows::core::Result<BSTR> {
+    match self.member_name(id) {
+      Some(name) => Ok(name.into()),
+      None => Err(DISP_E_MEMBERNOTFOUND.into()),
+    }
+  }
+
+  fn GetNextDispID(&self, _grfdex: u32, id: i32, pid: *mut i32) -> windows::core::Result<()> {
+    const S_FALSE: windows::core::HRESULT = windows::core::HRESULT(1);
+
+    match self.dynamic_members.borrow().next_after(id) {
+      // Safety: pid is a valid out-param pointer per the IDispatchEx contract
+      Some(next) => unsafe {
+        *pid = next;
+        Ok(())
+      },
+      None => Err(S_FALSE.into()),
+    }
+  }
+
+  fn GetNameSpaceParent(&self) -> windows::core::Result<IUnknown> {
+    Err(E_NOTIMPL.into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn round_trip(value: IpcValue) -> IpcValue {
+    let variant = variant_from_ipc_value(value).expect("marshal to VARIANT");
+    // Safety: variant was just built above and is fully initialized
+    let decoded = unsafe { ipc_value_from_variant(&variant) };
+    let mut variant = variant;
+    // Safety: variant is a valid, owned VARIANT
+    unsafe { VariantClear(&mut variant).ok() };
+    decoded
+  }
+
+  #[test]
+  fn get_or_ensure_without_ensure_misses_unknown_names() {
+    let mut members = DynamicMembers::new(10);
+    assert_eq!(members.get_or_ensure("foo", false), None);
+    assert!(members.dispids.is_empty());
+  }
+
+  #[test]
+  fn get_or_ensure_mints_dispids_past_the_starting_offset() {
+    let mut members = DynamicMembers::new(10);
+    assert_eq!(members.get_or_ensure("foo", true), Some(10));
+    assert_eq!(members.get_or_ensure("bar", true), Some(11));
+    assert_eq!(members.get_or_ensure("foo", false), Some(10));
+  }
+
+  #[test]
+  fn next_after_enumerates_in_insertion_order() {
+    let mut members = DynamicMembers::new(0);
+    members.get_or_ensure("foo", true);
+    members.get_or_ensure("bar", true);
+    members.get_or_ensure("baz", true);
+    assert_eq!(members.next_after(DISPID_STARTENUM), Some(0));
+    assert_eq!(members.next_after(0), Some(1));
+    assert_eq!(members.next_after(1), Some(2));
+    assert_eq!(members.next_after(2), None);
+  }
+
+  #[test]
+  fn remove_drops_the_name_and_its_stored_value() {
+    let mut members = DynamicMembers::new(0);
+    let dispid = members.get_or_ensure("foo", true).unwrap();
+    members.values.insert(dispid, IpcValue::String("hello".to_string()));
+    assert_eq!(members.remove("foo"), Some(dispid));
+    assert_eq!(members.remove("foo"), None);
+    assert!(!members.values.contains_key(&dispid));
+    assert!(members.names_in_order.is_empty());
+  }
+
+  #[test]
+  fn remove_by_dispid_removes_the_matching_name() {
+    let mut members = DynamicMembers::new(0);
+    let foo_dispid = members.get_or_ensure("foo", true).unwrap();
+    let bar_dispid = members.get_or_ensure("bar", true).unwrap();
+    assert!(members.remove_by_dispid(bar_dispid));
+    assert!(!members.remove_by_dispid(bar_dispid));
+    assert_eq!(members.next_after(DISPID_STARTENUM), Some(foo_dispid));
+  }
+
+  #[test]
+  fn round_trips_empty() {
+    assert!(matches!(round_trip(IpcValue::Empty), IpcValue::Empty));
+  }
+
+  #[test]
+  fn round_trips_null() {
+    assert!(matches!(round_trip(IpcValue::Null), IpcValue::Null));
+  }
+
+  #[test]
+  fn round_trips_bool() {
+    assert!(matches!(round_trip(IpcValue::Bool(true)), IpcValue::Bool(true)));
+    assert!(matches!(round_trip(IpcValue::Bool(false)), IpcValue::Bool(false)));
+  }
+
+  #[test]
+  fn round_trips_int() {
+    assert!(matches!(round_trip(IpcValue::Int(-42)), IpcValue::Int(-42)));
+  }
+
+  #[test]
+  fn round_trips_float() {
+    assert!(matches!(round_trip(IpcValue::Float(1.5)), IpcValue::Float(value) if value == 1.5));
+  }
+
+  #[test]
+  fn round_trips_string() {
+    match round_trip(IpcValue::String("hello".to_string())) {
+      IpcValue::String(value) => assert_eq!(value, "hello"),
+      other => panic!("expected String, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn round_trips_array_of_mixed_values() {
+    let original = IpcValue::Array(vec![
+      IpcValue::Int(1),
+      IpcValue::String("two".to_string()),
+      IpcValue::Bool(true),
+    ]);
+
+    match round_trip(original) {
+      IpcValue::Array(values) => {
+        assert_eq!(values.len(), 3);
+        assert!(matches!(values[0], IpcValue::Int(1)));
+        assert!(matches!(&values[1], IpcValue::String(s) if s == "two"));
+        assert!(matches!(values[2], IpcValue::Bool(true)));
+      }
+      other => panic!("expected Array, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn coerces_unrecognized_vartypes_to_string() {
+    let variant = variant_of(
+      windows::Win32::System::Ole::VT_UI2.0 as u16,
+      VARIANT_0_0_0 { uiVal: 5 },
+    );
+    // Safety: variant above is fully initialized; its vt (VT_UI2)
+    // deliberately isn't one ipc_value_from_variant decodes directly
+    let decoded = unsafe { ipc_value_from_variant(&variant) };
+    assert!(matches!(decoded, IpcValue::String(_)));
+  }
 }
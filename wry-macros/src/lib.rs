@@ -0,0 +1,181 @@
+// Copyright 2022 The RaptorFX Team, ReMod Software
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Internal proc-macros used by wry. Not part of the public API.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, FnArg, ItemTrait, Pat, ReturnType, TraitItem, Type};
+
+/// Maps a Rust type used in an `#[interface(...)]` trait signature to the
+/// `VARTYPE` tag that describes it to `CreateDispTypeInfo`.
+fn vartype_tag(ty: &Type) -> syn::Result<proc_macro2::TokenStream> {
+  let name = quote!(#ty).to_string();
+  Ok(match name.as_str() {
+    "BSTR" => quote!(::windows::Win32::System::Ole::VT_BSTR),
+    "u16" => quote!(::windows::Win32::System::Ole::VT_UI2),
+    "i32" => quote!(::windows::Win32::System::Ole::VT_I4),
+    "f64" => quote!(::windows::Win32::System::Ole::VT_R8),
+    "bool" => quote!(::windows::Win32::System::Ole::VT_BOOL),
+    "()" => quote!(::windows::Win32::System::Ole::VT_EMPTY),
+    _ => {
+      return Err(syn::Error::new_spanned(
+        ty,
+        format!(
+          "#[create_disp_type_info]: unsupported VARTYPE mapping for `{name}`; add it to vartype_tag"
+        ),
+      ))
+    }
+  })
+}
+
+/// Derives a free `create_disp_type_info` function for an
+/// `#[interface(...)]` host-object trait, emitted at the same scope as the
+/// trait itself.
+///
+/// This replaces the hand-written `PARAMDATA`/`METHODDATA`/`INTERFACEDATA`
+/// statics (and the `iMeth` vtable-slot arithmetic that goes with them) with
+/// a generated `fn create_disp_type_info() -> webview2_com::Result<ITypeInfo>`
+/// built directly from the trait's method signatures.
+#[proc_macro_attribute]
+pub fn create_disp_type_info(_args: TokenStream, input: TokenStream) -> TokenStream {
+  let item_trait = parse_macro_input!(input as ItemTrait);
+
+  match expand(&item_trait) {
+    Ok(generated_fn) => quote! {
+      #item_trait
+
+      #generated_fn
+    }
+    .into(),
+    Err(error) => {
+      let compile_error = error.to_compile_error();
+      quote! {
+        #item_trait
+
+        #compile_error
+      }
+      .into()
+    }
+  }
+}
+
+fn expand(item_trait: &ItemTrait) -> syn::Result<proc_macro2::TokenStream> {
+  let methods: Vec<_> = item_trait
+    .items
+    .iter()
+    .filter_map(|item| match item {
+      TraitItem::Fn(method) => Some(method),
+      _ => None,
+    })
+    .collect();
+
+  let mut param_statics = Vec::with_capacity(methods.len());
+  let mut method_entries = Vec::with_capacity(methods.len());
+  let params_ident = |index: usize| format_ident!("__CREATE_DISP_TYPE_INFO_PARAMS_{}", index);
+
+  for (index, method) in methods.iter().enumerate() {
+    let method_name = method.sig.ident.to_string();
+    let params_ident = params_ident(index);
+
+    let mut params = Vec::new();
+    for arg in &method.sig.inputs {
+      let FnArg::Typed(pat_type) = arg else {
+        continue;
+      };
+
+      let name = match &*pat_type.pat {
+        Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+        other => {
+          return Err(syn::Error::new_spanned(
+            other,
+            "#[create_disp_type_info]: unsupported parameter pattern",
+          ))
+        }
+      };
+      let vt = vartype_tag(&pat_type.ty)?;
+      params.push(quote! {
+        ::windows::Win32::System::Ole::PARAMDATA {
+          szName: pwstr!(#name),
+          vt: #vt.0 as u16,
+        }
+      });
+    }
+
+    let param_count = params.len();
+
+    param_statics.push(quote! {
+      static mut #params_ident: SyncStatic<[::windows::Win32::System::Ole::PARAMDATA; #param_count]> =
+        SyncStatic([#(#params),*]);
+    });
+
+    let vt_return = match &method.sig.output {
+      ReturnType::Default => vartype_tag(&syn::parse_quote!(()))?,
+      ReturnType::Type(_, ty) => vartype_tag(ty)?,
+    };
+
+    let dispid = index as i32;
+    // iMeth is this method's slot in the vtable, counted past IUnknown's
+    // three slots (QueryInterface/AddRef/Release) plus every earlier method
+    // declared on this trait.
+    let imeth_offset = index as u32;
+
+    method_entries.push(quote! {
+      ::windows::Win32::System::Ole::METHODDATA {
+        szName: pwstr!(#method_name),
+        ppdata: unsafe { &mut #params_ident.0 as *mut _ },
+        dispid: #dispid,
+        iMeth: (::std::mem::size_of::<::windows::core::IUnknownVtbl>()
+          / ::std::mem::size_of::<fn()>()
+          + #imeth_offset as usize) as u32,
+        cc: ::windows::Win32::System::Com::CC_STDCALL,
+        cArgs: #param_count as u32,
+        wFlags: ::windows::Win32::System::Ole::DISPATCH_METHOD as u16,
+        vtReturn: #vt_return.0 as u16,
+      }
+    });
+  }
+
+  let method_count = method_entries.len();
+  let fn_name = syn::Ident::new("create_disp_type_info", Span::call_site());
+
+  Ok(quote! {
+    #[allow(non_snake_case, clippy::identity_op)]
+    pub(crate) fn #fn_name() -> webview2_com::Result<::windows::Win32::System::Com::ITypeInfo> {
+      // Safety: we never mutate this trait's type information, so the below
+      // statics can be safely Sync.
+      #[repr(transparent)]
+      struct SyncStatic<T>(T);
+
+      unsafe impl<T> Sync for SyncStatic<T> {}
+
+      #(#param_statics)*
+
+      static mut __CREATE_DISP_TYPE_INFO_METHODS: SyncStatic<[::windows::Win32::System::Ole::METHODDATA; #method_count]> =
+        SyncStatic([#(#method_entries),*]);
+
+      static mut __CREATE_DISP_TYPE_INFO_INTERFACE: SyncStatic<::windows::Win32::System::Ole::INTERFACEDATA> =
+        SyncStatic(::windows::Win32::System::Ole::INTERFACEDATA {
+          pmethdata: unsafe { &mut __CREATE_DISP_TYPE_INFO_METHODS.0 as *mut _ },
+          cMembers: unsafe { __CREATE_DISP_TYPE_INFO_METHODS.0.len() as u32 },
+        });
+
+      // Safety: WinAPI calls are unsafe
+      unsafe {
+        let invariant_locale = ::windows::Win32::Globalization::LocaleNameToLCID(
+          ::windows::Win32::Globalization::LOCALE_NAME_INVARIANT,
+          0,
+        );
+        let mut type_info = None;
+        ::windows::Win32::System::Ole::CreateDispTypeInfo(
+          &mut __CREATE_DISP_TYPE_INFO_INTERFACE.0 as *mut _,
+          invariant_locale,
+          &mut type_info as *mut _,
+        )?;
+        Ok(type_info.unwrap())
+      }
+    }
+  })
+}